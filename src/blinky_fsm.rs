@@ -1,15 +1,39 @@
 use embedded_hal::digital::OutputPin;
 use typed_fsm::{state_machine, Transition};
 
+#[cfg(not(feature = "embassy"))]
 use rp235x_hal as hal;
 
+#[cfg(not(feature = "embassy"))]
 pub type LedPin = hal::gpio::Pin<
     hal::gpio::bank0::Gpio15,
     hal::gpio::FunctionSio<hal::gpio::SioOutput>,
     hal::gpio::PullDown,
 >;
 
-// Helper function to trigger ADC
+// The async build drives the LED through `embassy-rp`'s GPIO type instead; the
+// FSM's transition table is otherwise unchanged (see `embassy_app.rs`).
+#[cfg(feature = "embassy")]
+pub type LedPin = embassy_rp::gpio::Output<'static>;
+
+/// Watchdog handle kept alive (and fed) in `BlinkyContext` instead of being
+/// dropped right after clock setup, so a hung main loop or stuck ISR actually
+/// resets the board instead of silently hanging forever.
+#[cfg(not(feature = "embassy"))]
+pub type WatchdogHandle = hal::Watchdog;
+
+#[cfg(feature = "embassy")]
+pub type WatchdogHandle = embassy_rp::watchdog::Watchdog;
+
+// Helper function to trigger ADC: kicks a single-shot conversion from FSM
+// state entry. Only meaningful for the bare-metal build's register-level
+// single-shot ADC setup. The RTIC build triggers its conversions from the
+// `tick`/`adc_fifo` tasks instead (see `rtic_app.rs`), since reaching into
+// `ADC::ptr()` from here would race RTIC's own `Local` resource for the same
+// peripheral; the async build's `adc_task` and the `adc-dma` build's
+// continuous round-robin capture both produce results on their own schedule,
+// so there is nothing to trigger here either.
+#[cfg(not(any(feature = "embassy", feature = "adc-dma", feature = "rtic")))]
 fn trigger_adc() {
     unsafe {
         let adc_regs = &(*hal::pac::ADC::ptr());
@@ -17,11 +41,37 @@ fn trigger_adc() {
     }
 }
 
+#[cfg(any(feature = "embassy", feature = "adc-dma", feature = "rtic"))]
+fn trigger_adc() {}
+
+/// Default ADC threshold above which `LedOn` transitions into `HighValueWait`.
+/// Overridable at runtime via the `thresh <n>` USB command (see `usb_module.rs`).
+pub const DEFAULT_HIGH_THRESHOLD: u16 = 70;
+
+/// Default number of `TimerTick`s spent in `HighValueWait` before it is
+/// eligible to exit. Overridable at runtime via the `wait <n>` USB command.
+pub const DEFAULT_WAIT_LIMIT: u32 = 10;
+
 // FSM Context
 pub struct BlinkyContext {
     pub led: LedPin,
     pub wait_ticks: u32, // Counter for the wait state
     pub last_adc_value: u16, // Stores the last ADC value received
+    pub high_threshold: u16, // ADC value above which LedOn escalates to HighValueWait
+    pub wait_limit: u32, // Number of ticks HighValueWait must hold before it can exit
+    /// Count of events the ISR->main-loop queue dropped because it was full.
+    /// Populated from `main`'s atomic drop counter so it can be logged over USB.
+    pub dropped_events: u32,
+    /// Kept alive and fed from the tick path; see `WatchdogHandle`.
+    pub watchdog: WatchdogHandle,
+    /// Set by `Fault::entry`, one-shot, for whichever build's tick path owns
+    /// a real USB telemetry channel to pick up and send: the bare-metal
+    /// build writes it via `usb_module::write`, RTIC via its own
+    /// `usb_serial` resource, and Embassy via the `TELEMETRY` channel. Kept
+    /// as plain data here (like `dropped_events`) instead of calling into a
+    /// specific build's USB stack directly, since shared FSM code can't know
+    /// which one is active.
+    pub fault_message: Option<&'static str>,
 }
 
 // FSM Events
@@ -29,6 +79,10 @@ pub struct BlinkyContext {
 pub enum BlinkyEvent {
     TimerTick,
     AdcResult(u16),
+    /// Raised once at startup when `hardware::init` finds the previous reset
+    /// was caused by the watchdog, so the FSM starts in `Fault` instead of
+    /// resuming blindly into `LedOff`.
+    WatchdogWarn,
 }
 
 // State Machine Definition
@@ -46,6 +100,7 @@ state_machine! {
                 match evt {
                     BlinkyEvent::TimerTick => Transition::To(BlinkyFsm::LedOn),
                     BlinkyEvent::AdcResult(_) => Transition::None, // Ignored in this state
+                    BlinkyEvent::WatchdogWarn => Transition::To(BlinkyFsm::Fault),
                 }
             }
         },
@@ -56,16 +111,17 @@ state_machine! {
                 let _ = ctx.led.set_high();
                 trigger_adc();
             }
-            process: |_ctx, evt| {
+            process: |ctx, evt| {
                 match evt {
                     BlinkyEvent::TimerTick => Transition::To(BlinkyFsm::LedOff),
                     BlinkyEvent::AdcResult(val) => {
-                        if *val > 70 {
+                        if *val > ctx.high_threshold {
                             Transition::To(BlinkyFsm::HighValueWait)
                         } else {
                             Transition::None // ADC value okay, no state change
                         }
                     }
+                    BlinkyEvent::WatchdogWarn => Transition::To(BlinkyFsm::Fault),
                 }
             }
         },
@@ -81,9 +137,9 @@ state_machine! {
                     BlinkyEvent::TimerTick => {
                         trigger_adc(); // Trigger ADC to get a new value for exit condition
                         ctx.wait_ticks += 1;
-                        // Assuming TimerTick happens every 200ms.
-                        // 2 seconds / 200ms = 10 ticks.
-                        if ctx.wait_ticks >= 10 && ctx.last_adc_value <= 70 {
+                        // Assuming TimerTick happens every 200ms; `wait_limit` ticks
+                        // at the default of 10 works out to 2 seconds.
+                        if ctx.wait_ticks >= ctx.wait_limit && ctx.last_adc_value <= ctx.high_threshold {
                             Transition::To(BlinkyFsm::LedOff) // Time up AND ADC value is safe
                         } else {
                             Transition::None // Keep waiting
@@ -93,6 +149,39 @@ state_machine! {
                         ctx.last_adc_value = *val; // Update last known ADC value
                         Transition::None // Stay in this state
                     },
+                    BlinkyEvent::WatchdogWarn => Transition::To(BlinkyFsm::Fault),
+                }
+            }
+        },
+
+        // State: Fault (entered on a watchdog-induced reset). Toggles the LED
+        // once per `TimerTick`, same as `LedOn`/`LedOff`; there's no faster
+        // clock available to this shared, synchronous code without blocking
+        // it (a busy-wait here would stall the RTIC `tick` ISR and the
+        // Embassy executor's single `fsm_task` for the duration, starving
+        // every other task/interrupt — see git history for the blocking
+        // version this replaced). Recovery requires a fresh reset, since a
+        // reset reason that got us here once is worth investigating rather
+        // than silently clearing.
+        Fault => {
+            entry: |ctx| {
+                let _ = ctx.led.set_high();
+                ctx.wait_ticks = 0;
+                ctx.fault_message = Some("Fault: watchdog reset detected\r\n");
+            }
+            process: |ctx, evt| {
+                match evt {
+                    BlinkyEvent::TimerTick => {
+                        ctx.wait_ticks = ctx.wait_ticks.wrapping_add(1);
+                        if ctx.wait_ticks % 2 == 0 {
+                            let _ = ctx.led.set_high();
+                        } else {
+                            let _ = ctx.led.set_low();
+                        }
+                        Transition::None
+                    },
+                    BlinkyEvent::AdcResult(_) => Transition::None,
+                    BlinkyEvent::WatchdogWarn => Transition::None, // already in Fault
                 }
             }
         }
@@ -0,0 +1,191 @@
+//! Embassy Application Variant
+//!
+//! This module provides an alternative, fully async entry point built on
+//! `embassy-rp` + `embassy-executor`, enabled via the `embassy` cargo feature.
+//! Two tasks feed a single `BlinkyEvent` channel: a `ticker` task firing every
+//! 200 ms and an `adc_task` awaiting conversions. A third, `usb_task`, drives
+//! `embassy_usb` CDC-ACM; it discards inbound bytes and sends no events. A
+//! single `fsm_task` awaits the channel and calls
+//! `fsm.dispatch`, so the FSM itself stays synchronous and unchanged, and since
+//! only that one task ever touches it, no `critical_section`/`Mutex` is needed.
+//! A second channel, `TELEMETRY`, carries the per-tick `State: ...` line back
+//! from `fsm_task` to `usb_task`, which writes it out over the CDC-ACM class
+//! alongside its existing inbound-byte handling.
+//!
+//! Build with `--no-default-features --features embassy` (mutually exclusive
+//! with the default polling build in `main.rs` and with the `rtic` feature).
+
+#![cfg(feature = "embassy")]
+
+use core::fmt::Write as FmtWrite;
+
+use embassy_executor::Spawner;
+use embassy_rp::adc::{Adc, Channel as AdcChannel, Config as AdcConfig, InterruptHandler as AdcInterruptHandler};
+use embassy_rp::gpio::{Level, Output};
+use embassy_rp::peripherals::USB;
+use embassy_rp::usb::{Driver, InterruptHandler};
+use embassy_rp::watchdog::Watchdog;
+use embassy_sync::blocking_mutex::raw::ThreadModeRawMutex;
+use embassy_sync::channel::Channel;
+use embassy_time::{Duration, Ticker};
+use embassy_usb::class::cdc_acm::{CdcAcmClass, State};
+use embassy_usb::{Builder, Config as UsbConfig};
+use embassy_futures::select::{select, Either};
+use heapless::String;
+
+use crate::blinky_fsm::{BlinkyContext, BlinkyEvent, BlinkyFsm};
+
+embassy_rp::bind_interrupts!(struct Irqs {
+    USBCTRL_IRQ => InterruptHandler<USB>;
+    ADC_IRQ_FIFO => AdcInterruptHandler;
+});
+
+/// Event channel: every task that produces `BlinkyEvent`s sends here; only
+/// `fsm_task` ever receives, so the FSM stays single-owner.
+static EVENTS: Channel<ThreadModeRawMutex, BlinkyEvent, 8> = Channel::new();
+
+/// Telemetry channel: `fsm_task` sends its per-tick `State: ...` line here;
+/// `usb_task` is the only receiver, and writes it out over the CDC-ACM class.
+static TELEMETRY: Channel<ThreadModeRawMutex, String<64>, 4> = Channel::new();
+
+/// Entry point for the async build, invoked by the `#[embassy_executor::main]`
+/// wrapper in `main.rs` when the `embassy` feature is selected.
+pub async fn run(spawner: Spawner) {
+    let p = embassy_rp::init(Default::default());
+
+    let led = Output::new(p.PIN_15, Level::Low);
+
+    let adc = Adc::new(p.ADC, Irqs, AdcConfig::default());
+    let adc_pin = AdcChannel::new_pin(p.PIN_26, embassy_rp::gpio::Pull::None);
+
+    let driver = Driver::new(p.USB, Irqs);
+
+    // Kept alive and fed from `fsm_task` instead of being dropped here; see
+    // `BlinkyContext::watchdog`. `embassy-rp`'s `Watchdog` doesn't expose a
+    // reset-reason check the way `rp235x_hal::Watchdog::caused_reboot` does,
+    // so the async build always starts into `LedOff` rather than `Fault`.
+    let mut watchdog = Watchdog::new(p.WATCHDOG);
+    watchdog.start(Duration::from_millis(500));
+
+    spawner.spawn(ticker_task()).unwrap();
+    spawner.spawn(adc_task(adc, adc_pin)).unwrap();
+    spawner.spawn(usb_task(driver)).unwrap();
+    spawner.spawn(fsm_task(led, watchdog)).unwrap();
+}
+
+/// Sends `TimerTick` every 200 ms, replacing the busy-poll of `timer.get_counter()`
+/// used by the bare-metal build.
+#[embassy_executor::task]
+async fn ticker_task() {
+    let mut ticker = Ticker::every(embassy_time::Duration::from_millis(200));
+    loop {
+        ticker.next().await;
+        EVENTS.send(BlinkyEvent::TimerTick).await;
+    }
+}
+
+/// Awaits ADC conversions and forwards each result as an `AdcResult` event.
+#[embassy_executor::task]
+async fn adc_task(mut adc: Adc<'static, embassy_rp::adc::Async>, mut pin: AdcChannel<'static>) {
+    loop {
+        if let Ok(value) = adc.read(&mut pin).await {
+            EVENTS.send(BlinkyEvent::AdcResult(value)).await;
+        }
+    }
+}
+
+/// Drives the `embassy_usb` CDC-ACM class: discards inbound bytes (a command
+/// protocol can be layered on top the same way `usb_module.rs` does for the
+/// polling build) and writes out whatever `fsm_task` sends on `TELEMETRY`.
+#[embassy_executor::task]
+async fn usb_task(driver: Driver<'static, USB>) {
+    let mut config = UsbConfig::new(0x16c0, 0x27dd);
+    config.manufacturer = Some("Raspberry Pi");
+    config.product = Some("Pico 2 W ADC Demo (Embassy)");
+    config.serial_number = Some("ADC001");
+
+    let mut config_descriptor = [0; 256];
+    let mut bos_descriptor = [0; 256];
+    let mut control_buf = [0; 64];
+    let mut state = State::new();
+
+    let mut builder = Builder::new(
+        driver,
+        config,
+        &mut config_descriptor,
+        &mut bos_descriptor,
+        &mut [],
+        &mut control_buf,
+    );
+
+    let mut class = CdcAcmClass::new(&mut builder, &mut state, 64);
+    let mut usb = builder.build();
+
+    let usb_fut = usb.run();
+    let io_fut = async {
+        let mut buf = [0u8; 64];
+        loop {
+            class.wait_connection().await;
+            loop {
+                match select(class.read_packet(&mut buf), TELEMETRY.receive()).await {
+                    Either::First(Ok(_)) => {}
+                    Either::First(Err(_)) => break,
+                    Either::Second(msg) => {
+                        if class.write_packet(msg.as_bytes()).await.is_err() {
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+    };
+
+    embassy_futures::join::join(usb_fut, io_fut).await;
+}
+
+/// Owns the FSM+context exclusively and drains `EVENTS`, replacing every ISR
+/// that used to reach into `GLOBAL_STATE` under `critical_section::with`.
+#[embassy_executor::task]
+async fn fsm_task(led: Output<'static>, watchdog: Watchdog) {
+    let mut ctx = BlinkyContext {
+        led,
+        wait_ticks: 0,
+        last_adc_value: 0,
+        high_threshold: crate::blinky_fsm::DEFAULT_HIGH_THRESHOLD,
+        wait_limit: crate::blinky_fsm::DEFAULT_WAIT_LIMIT,
+        dropped_events: 0,
+        watchdog,
+        fault_message: None,
+    };
+    let mut fsm = BlinkyFsm::LedOff;
+    fsm.init(&mut ctx);
+
+    loop {
+        let event = EVENTS.receive().await;
+        fsm.dispatch(&mut ctx, &event);
+
+        // Every event reaching here means this task is alive and draining
+        // `EVENTS`, so it's the natural place to feed the watchdog.
+        ctx.watchdog.feed();
+
+        if let Some(reason) = ctx.fault_message.take() {
+            let mut reason_msg: String<64> = String::new();
+            if reason_msg.push_str(reason).is_ok() {
+                // Never wait on `TELEMETRY`: with no USB host connected nothing
+                // drains it, and blocking here would stop the watchdog feed.
+                let _ = TELEMETRY.try_send(reason_msg);
+            }
+        }
+
+        let state_str = match fsm {
+            BlinkyFsm::LedOff => "OFF",
+            BlinkyFsm::LedOn => "ON",
+            BlinkyFsm::HighValueWait => "WAIT_HIGH_VALUE",
+            BlinkyFsm::Fault => "FAULT",
+        };
+        let mut msg: String<64> = String::new();
+        if FmtWrite::write_fmt(&mut msg, format_args!("State: {}\r\n", state_str)).is_ok() {
+            let _ = TELEMETRY.try_send(msg);
+        }
+    }
+}
@@ -7,28 +7,62 @@
 use rp235x_hal as hal;
 use hal::pac;
 
+#[cfg(feature = "adc-dma")]
+use hal::dma::{single_buffer, DMAExt};
+
 use crate::blinky_fsm::LedPin;
 use crate::usb_module;
 
 /// External crystal frequency used by the Raspberry Pi Pico 2 W.
 const XTAL_FREQ_HZ: u32 = 12_000_000u32;
 
+/// Number of ADC samples DMA'd into the ring buffer before they're averaged
+/// into a single smoothed `BlinkyEvent::AdcResult`. Debounces the
+/// `high_threshold` comparison against sample noise.
+#[cfg(feature = "adc-dma")]
+pub const ADC_DMA_SAMPLES: usize = 16;
+
+/// In-flight DMA transfer draining the ADC FIFO into the ring buffer. Stored
+/// by the caller of `init_with_dma` (e.g. in a `Mutex<RefCell<Option<_>>>`
+/// alongside `GLOBAL_ALARM`) so `DMA_IRQ_0` can `wait()` on it, average the
+/// buffer, and restart capture.
+#[cfg(feature = "adc-dma")]
+pub type AdcDmaTransfer = single_buffer::Transfer<
+    hal::dma::Channel<hal::dma::CH0>,
+    hal::adc::AdcFifo<'static, u16>,
+    &'static mut [u16; ADC_DMA_SAMPLES],
+>;
+
+/// How often `TIMER_IRQ_0` rearms itself and enqueues `BlinkyEvent::TimerTick`.
+pub(crate) const TICK_INTERVAL_MS: u32 = 200;
+
+/// Watchdog timeout. Comfortably longer than `TICK_INTERVAL_MS` so a single
+/// slow tick doesn't trip it, but short enough that a genuinely hung main
+/// loop or stuck ISR reboots quickly.
+const WATCHDOG_TIMEOUT_MS: u32 = 500;
+
 /// Initializes the entire hardware stack.
 ///
 /// This function:
 /// 1.  Takes ownership of the raw PAC peripherals.
 /// 2.  Configures the Watchdog and Clocks (System & USB).
-/// 3.  Initializes the Microsecond Timer.
+/// 3.  Initializes the Microsecond Timer and arms a periodic `TIMER0` alarm.
 /// 4.  Configures GPIO pins (LED).
 /// 5.  Sets up the ADC for Interrupt-driven single-shot mode.
 /// 6.  Initializes the USB Serial module.
 ///
 /// # Returns
-/// A tuple containing the initialized peripherals needed by `main`: `(LedPin, Timer)`.
-pub fn init() -> (LedPin, hal::Timer<hal::timer::CopyableTimer0>) {
+/// `(LedPin, Alarm0, Watchdog, bool)` — the bool is `true` if the previous
+/// reset was caused by the watchdog itself, so `main` can raise
+/// `BlinkyEvent::WatchdogWarn` and start the FSM in `Fault` instead of
+/// resuming blindly into `LedOff`. The watchdog is kept running (not
+/// dropped after clocks init) and must be fed periodically by the caller via
+/// `BlinkyContext::watchdog`, or it will reset the board.
+pub fn init() -> (LedPin, hal::timer::Alarm0, hal::Watchdog, bool) {
     // 1. Take ownership of raw peripherals
     let mut pac = pac::Peripherals::take().unwrap();
     let mut watchdog = hal::Watchdog::new(pac.WATCHDOG);
+    let watchdog_caused_reset = watchdog.caused_reboot();
 
     // 2. Configure Clocks
     let clocks = hal::clocks::init_clocks_and_plls(
@@ -42,8 +76,19 @@ pub fn init() -> (LedPin, hal::Timer<hal::timer::CopyableTimer0>) {
     )
     .unwrap();
 
-    // 3. Configure Timer (Microsecond precision)
-    let timer = hal::Timer::new_timer0(pac.TIMER0, &mut pac.RESETS, &clocks);
+    // 3. Configure Timer (Microsecond precision) and arm a periodic alarm.
+    // Interrupt-driven ticks replace the busy-polling of `timer.get_counter()`
+    // that used to live in `main`'s loop, freeing it to `wfi()` between events.
+    let mut timer = hal::Timer::new_timer0(pac.TIMER0, &mut pac.RESETS, &clocks);
+    let mut alarm = timer.alarm_0().unwrap();
+    alarm
+        .schedule(fugit::ExtU32::millis(TICK_INTERVAL_MS))
+        .unwrap();
+    alarm.enable_interrupt();
+
+    unsafe {
+        cortex_m::peripheral::NVIC::unmask(pac::Interrupt::TIMER_IRQ_0);
+    }
 
     // 4. Configure GPIOs
     let sio = hal::Sio::new(pac.SIO);
@@ -92,6 +137,116 @@ pub fn init() -> (LedPin, hal::Timer<hal::timer::CopyableTimer0>) {
         &mut pac.RESETS,
     );
 
+    // 7. Arm the watchdog. Started last so the bring-up above (which can
+    // legitimately take a little while on some boards) never trips it before
+    // the main loop gets a chance to start feeding it.
+    watchdog.start(fugit::ExtU32::millis(WATCHDOG_TIMEOUT_MS));
+
     // Return ready-to-use hardware
-    (led_pin, timer)
+    (led_pin, alarm, watchdog, watchdog_caused_reset)
+}
+
+/// Like [`init`], but configures the ADC for continuous round-robin capture
+/// drained by DMA into a ring buffer, instead of single-shot-per-tick FIFO
+/// interrupts. Selected by the `adc-dma` cargo feature; mutually exclusive
+/// with `init`'s register-level `trigger_adc()` dance (see `blinky_fsm.rs`).
+///
+/// # Returns
+/// `(LedPin, Alarm0, AdcDmaTransfer, Watchdog, bool)` — the caller stashes
+/// the transfer handle so its completion (in `DMA_IRQ_0`) can be averaged
+/// and fed to the FSM as a single smoothed `BlinkyEvent::AdcResult`. The
+/// trailing `Watchdog`/`bool` pair behaves exactly as in [`init`].
+#[cfg(feature = "adc-dma")]
+pub fn init_with_dma() -> (LedPin, hal::timer::Alarm0, AdcDmaTransfer, hal::Watchdog, bool) {
+    // 1. Take ownership of raw peripherals
+    let mut pac = pac::Peripherals::take().unwrap();
+    let mut watchdog = hal::Watchdog::new(pac.WATCHDOG);
+    let watchdog_caused_reset = watchdog.caused_reboot();
+
+    // 2. Configure Clocks
+    let clocks = hal::clocks::init_clocks_and_plls(
+        XTAL_FREQ_HZ,
+        pac.XOSC,
+        pac.CLOCKS,
+        pac.PLL_SYS,
+        pac.PLL_USB,
+        &mut pac.RESETS,
+        &mut watchdog,
+    )
+    .unwrap();
+
+    // 3. Configure Timer (Microsecond precision) and arm a periodic alarm.
+    let mut timer = hal::Timer::new_timer0(pac.TIMER0, &mut pac.RESETS, &clocks);
+    let mut alarm = timer.alarm_0().unwrap();
+    alarm
+        .schedule(fugit::ExtU32::millis(TICK_INTERVAL_MS))
+        .unwrap();
+    alarm.enable_interrupt();
+
+    unsafe {
+        cortex_m::peripheral::NVIC::unmask(pac::Interrupt::TIMER_IRQ_0);
+    }
+
+    // 4. Configure GPIOs
+    let sio = hal::Sio::new(pac.SIO);
+    let pins = hal::gpio::Pins::new(
+        pac.IO_BANK0,
+        pac.PADS_BANK0,
+        sio.gpio_bank0,
+        &mut pac.RESETS,
+    );
+
+    let led_pin = pins.gpio15.into_push_pull_output();
+
+    // 5. Configure ADC for continuous round-robin capture, DMA'd into a ring
+    // buffer instead of interrupting once per sample.
+    let adc = hal::Adc::new(pac.ADC, &mut pac.RESETS);
+    let adc_pin = hal::adc::AdcPin::new(pins.gpio26).unwrap();
+
+    let fifo = adc
+        .build_fifo()
+        .set_channel(adc_pin)
+        .clock_divider(0, 0)
+        .enable_dma()
+        .start();
+
+    // Safety: this static is only ever touched through the DMA transfer
+    // created below, which takes exclusive ownership of the reference.
+    static mut ADC_RING: [u16; ADC_DMA_SAMPLES] = [0; ADC_DMA_SAMPLES];
+    let buffer = unsafe { &mut *core::ptr::addr_of_mut!(ADC_RING) };
+
+    let dma = pac.DMA.split(&mut pac.RESETS);
+    let transfer = single_buffer::Config::new(dma.ch0, fifo, buffer).start();
+
+    unsafe {
+        cortex_m::peripheral::NVIC::unmask(pac::Interrupt::DMA_IRQ_0);
+    }
+
+    // 6. Configure USB Serial (via module)
+    usb_module::init(
+        pac.USB,
+        pac.USB_DPRAM,
+        clocks.usb_clock,
+        &mut pac.RESETS,
+    );
+
+    // 7. Arm the watchdog; see `init` for the rationale on ordering.
+    watchdog.start(fugit::ExtU32::millis(WATCHDOG_TIMEOUT_MS));
+
+    (led_pin, alarm, transfer, watchdog, watchdog_caused_reset)
+}
+
+/// Average a completed DMA capture and immediately restart it.
+///
+/// Called from `DMA_IRQ_0` with the just-finished transfer; returns the
+/// smoothed sample plus the restarted transfer to store back in the caller's
+/// global.
+#[cfg(feature = "adc-dma")]
+pub fn restart_adc_dma_averaged(transfer: AdcDmaTransfer) -> (u16, AdcDmaTransfer) {
+    let (ch0, fifo, buffer) = transfer.wait();
+
+    let sum: u32 = buffer.iter().map(|&sample| sample as u32).sum();
+    let average = (sum / ADC_DMA_SAMPLES as u32) as u16;
+
+    (average, single_buffer::Config::new(ch0, fifo, buffer).start())
 }
\ No newline at end of file
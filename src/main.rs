@@ -15,9 +15,11 @@
 // --- Imports ---
 use core::cell::RefCell;
 use core::fmt::Write as FmtWrite;
+use core::sync::atomic::{AtomicU32, Ordering};
 use critical_section::Mutex;
 use defmt ::*;
 use defmt_rtt as _;
+use heapless::spsc::{Producer, Queue};
 use heapless::String;
 use panic_probe as _;
 
@@ -28,12 +30,32 @@ use blinky_fsm::{BlinkyContext, BlinkyEvent, BlinkyFsm};
 mod usb_module;
 mod hardware;
 
+// The RTIC variant replaces this module's `#[entry]`/ISR setup wholesale with
+// resource-managed tasks; see `rtic_app.rs` for details. Build with
+// `--no-default-features --features rtic` to select it.
+#[cfg(feature = "rtic")]
+mod rtic_app;
+
+// The embassy variant runs a fully async executor instead of the `#[entry]`
+// polling loop below; see `embassy_app.rs`. Build with `--no-default-features
+// --features embassy` to select it.
+#[cfg(feature = "embassy")]
+mod embassy_app;
+
+#[cfg(feature = "embassy")]
+#[embassy_executor::main]
+async fn embassy_main(spawner: embassy_executor::Spawner) {
+    embassy_app::run(spawner).await;
+}
+
 // --- HAL Selection ---
 use rp235x_hal as hal;
+#[cfg(not(any(feature = "rtic", feature = "embassy")))]
 use hal::entry;
 use hal::pac;
 
 // Select appropriate interrupt macro based on chip architecture
+#[cfg(not(any(feature = "rtic", feature = "embassy")))]
 use rp235x_hal::pac::interrupt;
 
 // --- Bootloader Configuration ---
@@ -43,101 +65,234 @@ use rp235x_hal::pac::interrupt;
 pub static IMAGE_DEF: hal::block::ImageDef = hal::block::ImageDef::secure_exe();
 
 // --- Shared State ---
+//
+// The FSM and its context are owned exclusively by the main loop; ISRs never
+// touch them. `ADC_IRQ_FIFO`, `TIMER_IRQ_0` and (with `adc-dma`) `DMA_IRQ_0`
+// only push a `BlinkyEvent` onto `EVENT_QUEUE` — a short, allocation-free
+// push under a brief critical section that guards the shared producer — and
+// the main loop drains it and calls `dispatch()`
+// outside of any interrupt context.
 
-/// Wrapper struct to unify FSM and Context into a single global resource.
-/// This reduces locking overhead (one Mutex vs two) and simplifies data management.
-pub struct AppState {
-    fsm: BlinkyFsm,
-    ctx: BlinkyContext,
-}
+/// Depth of the ISR -> main-loop event queue. Sized generously relative to
+/// how many events can arrive between main-loop iterations.
+const EVENT_QUEUE_LEN: usize = 16;
+
+// Backing storage for the SPSC queue; split once at startup into a `Producer`
+// reachable from interrupt context and a `Consumer` owned by the main loop.
+#[cfg(not(any(feature = "rtic", feature = "embassy")))]
+static mut EVENT_QUEUE: Queue<BlinkyEvent, EVENT_QUEUE_LEN> = Queue::new();
+
+// The producer half is shared across `ADC_IRQ_FIFO`/`TIMER_IRQ_0`/`DMA_IRQ_0`;
+// a critical section serializes access between them the same way it already
+// serializes access to the other ISR-reachable globals below.
+#[cfg(not(any(feature = "rtic", feature = "embassy")))]
+static GLOBAL_PRODUCER: Mutex<RefCell<Option<Producer<'static, BlinkyEvent, EVENT_QUEUE_LEN>>>> =
+    Mutex::new(RefCell::new(None));
 
-// Global Application State (Mutex protected for ISR access)
-static GLOBAL_STATE: Mutex<RefCell<Option<AppState>>> = Mutex::new(RefCell::new(None));
+// Counts events dropped because `EVENT_QUEUE` was full. Folded into
+// `BlinkyContext::dropped_events` by the main loop so it can be logged over
+// USB; a plain atomic since only a count, not the data itself, crosses the
+// ISR/main-loop boundary here.
+#[cfg(not(any(feature = "rtic", feature = "embassy")))]
+static DROPPED_EVENTS: AtomicU32 = AtomicU32::new(0);
+
+// The TIMER0 alarm driving periodic ticks from `TIMER_IRQ_0`, rearmed on every
+// firing. Lives in a global since it's only ever touched from interrupt
+// context.
+#[cfg(not(any(feature = "rtic", feature = "embassy")))]
+static GLOBAL_ALARM: Mutex<RefCell<Option<hal::timer::Alarm0>>> = Mutex::new(RefCell::new(None));
+
+// In-flight ADC DMA transfer; only populated when the `adc-dma` feature
+// replaces the single-shot `ADC_IRQ_FIFO` path with continuous capture.
+#[cfg(feature = "adc-dma")]
+static GLOBAL_ADC_DMA: Mutex<RefCell<Option<hardware::AdcDmaTransfer>>> = Mutex::new(RefCell::new(None));
+
+/// Pushes `event` onto `EVENT_QUEUE`, counting a drop if it's full. Called
+/// from interrupt context only, already inside a `critical_section::with`.
+#[cfg(not(any(feature = "rtic", feature = "embassy")))]
+fn push_event(cs: critical_section::CriticalSection, event: BlinkyEvent) {
+    let mut producer_guard = GLOBAL_PRODUCER.borrow_ref_mut(cs);
+    if let Some(producer) = producer_guard.as_mut() {
+        if producer.enqueue(event).is_err() {
+            DROPPED_EVENTS.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
 
 /// Entry point.
+#[cfg(not(any(feature = "rtic", feature = "embassy")))]
 #[entry]
 fn main() -> ! {
     info!("Program start");
 
     // 1. Initialize Hardware Stack (Clocks, GPIO, Timer, ADC, USB)
     // Now returns a tuple directly, removing the ephemeral `Hardware` struct.
-    let (led_pin, timer) = hardware::init();
+    // The timer is consumed internally; we only get back the armed alarm.
+    // With the `adc-dma` feature, `init_with_dma` also hands back the
+    // in-flight DMA transfer draining the ADC FIFO.
+    #[cfg(not(feature = "adc-dma"))]
+    let (led_pin, alarm, watchdog, watchdog_caused_reset) = hardware::init();
+    #[cfg(feature = "adc-dma")]
+    let (led_pin, alarm, adc_dma, watchdog, watchdog_caused_reset) = hardware::init_with_dma();
 
     // 2. Initialize Application State (FSM)
     // Create context and initial state immediately.
-    let mut ctx = BlinkyContext { 
-        led: led_pin, 
-        wait_ticks: 0, 
-        last_adc_value: 0 
+    let mut ctx = BlinkyContext {
+        led: led_pin,
+        wait_ticks: 0,
+        last_adc_value: 0,
+        high_threshold: blinky_fsm::DEFAULT_HIGH_THRESHOLD,
+        wait_limit: blinky_fsm::DEFAULT_WAIT_LIMIT,
+        dropped_events: 0,
+        watchdog,
+        fault_message: None,
     };
     let mut fsm = BlinkyFsm::LedOff;
     fsm.init(&mut ctx);
 
-    // 3. Publish to Global State
-    // Bundled initialization eliminates the "dance" of separate Options.
+    // If the previous reset was the watchdog firing, go straight to `Fault`
+    // instead of resuming into `LedOff` as though nothing happened.
+    if watchdog_caused_reset {
+        fsm.dispatch(&mut ctx, &BlinkyEvent::WatchdogWarn);
+        if let Some(reason) = ctx.fault_message.take() {
+            usb_module::write(reason.as_bytes());
+        }
+    }
+
+    // 3. Split the event queue and publish the producer + remaining hardware
+    // handles for interrupt context to reach. `fsm`/`ctx` themselves are kept
+    // as plain local variables below: no ISR ever touches them.
+    // Safety: `split` is called exactly once, before any interrupt that could
+    // race it is unmasked.
+    let (producer, mut consumer) = unsafe {
+        #[allow(static_mut_refs)]
+        EVENT_QUEUE.split()
+    };
     critical_section::with(|cs| {
-        GLOBAL_STATE.borrow_ref_mut(cs).replace(AppState { fsm, ctx });
+        GLOBAL_PRODUCER.borrow_ref_mut(cs).replace(producer);
+        GLOBAL_ALARM.borrow_ref_mut(cs).replace(alarm);
+        #[cfg(feature = "adc-dma")]
+        GLOBAL_ADC_DMA.borrow_ref_mut(cs).replace(adc_dma);
     });
 
-    let mut last_toggle = timer.get_counter();
-
     // 4. Main Application Loop
+    // Ticks now arrive via `TIMER_IRQ_0` instead of polling `timer.get_counter()`,
+    // so the core can sleep between events.
     loop {
-        let current_time = timer.get_counter();
-        
-        // Periodic Task: Timer Tick (every 200ms)
-        if current_time.ticks().saturating_sub(last_toggle.ticks()) >= 200_000 {
-            last_toggle = current_time;
-            
-            let mut current_state_str = "Unknown";
-
-            // Dispatch TimerTick Event using unified global state
-            critical_section::with(|cs| {
-                let mut state_guard = GLOBAL_STATE.borrow_ref_mut(cs);
-                
-                if let Some(state) = state_guard.as_mut() {
-                    state.fsm.dispatch(&mut state.ctx, &BlinkyEvent::TimerTick);
-                    
-                    match state.fsm {
-                        BlinkyFsm::LedOff => current_state_str = "OFF",
-                        BlinkyFsm::LedOn => current_state_str = "ON",
-                        BlinkyFsm::HighValueWait => current_state_str = "WAIT_HIGH_VALUE",
-                    }
+        cortex_m::asm::wfi();
+
+        // Drain events queued by the ISRs and dispatch them outside of any
+        // interrupt context.
+        while let Some(event) = consumer.dequeue() {
+            fsm.dispatch(&mut ctx, &event);
+            ctx.dropped_events = DROPPED_EVENTS.load(Ordering::Relaxed);
+
+            if let Some(reason) = ctx.fault_message.take() {
+                usb_module::write(reason.as_bytes());
+            }
+
+            if matches!(event, BlinkyEvent::TimerTick) {
+                // Every tick reaching here means the main loop is alive and
+                // dispatching, so it's the natural place to feed the watchdog.
+                ctx.watchdog.feed();
+
+                let state_str = match fsm {
+                    BlinkyFsm::LedOff => "OFF",
+                    BlinkyFsm::LedOn => "ON",
+                    BlinkyFsm::HighValueWait => "WAIT_HIGH_VALUE",
+                    BlinkyFsm::Fault => "FAULT",
+                };
+
+                // Log state (and any queue drops) to USB
+                let mut msg: String<64> = String::new();
+                if FmtWrite::write_fmt(
+                    &mut msg,
+                    format_args!("State: {} Dropped: {}\r\n", state_str, ctx.dropped_events),
+                )
+                .is_ok()
+                {
+                    usb_module::write(msg.as_bytes());
                 }
-            });
+            }
+        }
 
-            // Log state to USB
-            let mut msg: String<64> = String::new();
-            if FmtWrite::write_fmt(&mut msg, format_args!("State: {}\r\n", current_state_str)).is_ok() {
-                usb_module::write(msg.as_bytes());
+        // Apply any console commands queued by USBCTRL_IRQ (injected events or
+        // runtime-tunable parameters). Same main-loop ownership as above, so
+        // this can dispatch directly with no locking.
+        while let Some(cmd) = usb_module::take_command() {
+            match cmd {
+                usb_module::UsbCommand::Inject(evt) => {
+                    fsm.dispatch(&mut ctx, &evt);
+                }
+                usb_module::UsbCommand::SetThreshold(value) => {
+                    ctx.high_threshold = value;
+                }
+                usb_module::UsbCommand::SetWaitLimit(value) => {
+                    ctx.wait_limit = value;
+                }
             }
         }
     }
 }
 
 // --- Interrupt Handlers ---
+// The rtic feature declares its tasks inside `rtic_app.rs` instead.
+
+/// Rearms the TIMER0 alarm and enqueues `BlinkyEvent::TimerTick` for the main
+/// loop to dispatch, replacing the main loop's busy-polling of
+/// `timer.get_counter()`.
+#[cfg(not(any(feature = "rtic", feature = "embassy")))]
+#[allow(non_snake_case)]
+#[interrupt]
+fn TIMER_IRQ_0() {
+    critical_section::with(|cs| {
+        if let Some(alarm) = GLOBAL_ALARM.borrow_ref_mut(cs).as_mut() {
+            alarm.clear_interrupt();
+            let _ = alarm.schedule(fugit::ExtU32::millis(hardware::TICK_INTERVAL_MS));
+        }
+
+        push_event(cs, BlinkyEvent::TimerTick);
+    });
+}
 
+#[cfg(not(any(feature = "rtic", feature = "embassy", feature = "adc-dma")))]
 #[allow(non_snake_case)]
 #[interrupt]
 fn ADC_IRQ_FIFO() {
     unsafe {
         let adc_regs = &(*pac::ADC::ptr());
-        
+
         if adc_regs.fcs().read().level().bits() > 0 {
             let value = adc_regs.fifo().read().val().bits();
-            
-            // Dispatch AdcResult Event using unified global state
+
             critical_section::with(|cs| {
-                let mut state_guard = GLOBAL_STATE.borrow_ref_mut(cs);
-                
-                if let Some(state) = state_guard.as_mut() {
-                    state.fsm.dispatch(&mut state.ctx, &BlinkyEvent::AdcResult(value as u16));
-                }
+                push_event(cs, BlinkyEvent::AdcResult(value as u16));
             });
         }
     }
 }
 
+/// Fires once the DMA transfer has drained `hardware::ADC_DMA_SAMPLES` ADC
+/// conversions into the ring buffer. Averages them into a single smoothed
+/// value, restarts capture, and enqueues one `BlinkyEvent::AdcResult` instead
+/// of one interrupt per raw sample.
+#[cfg(feature = "adc-dma")]
+#[allow(non_snake_case)]
+#[interrupt]
+fn DMA_IRQ_0() {
+    critical_section::with(|cs| {
+        let mut transfer_guard = GLOBAL_ADC_DMA.borrow_ref_mut(cs);
+
+        if let Some(transfer) = transfer_guard.take() {
+            let (average, restarted) = hardware::restart_adc_dma_averaged(transfer);
+            transfer_guard.replace(restarted);
+            drop(transfer_guard);
+
+            push_event(cs, BlinkyEvent::AdcResult(average));
+        }
+    });
+}
+
 // --- Metadata ---
 
 #[unsafe(link_section = ".bi_entries")]
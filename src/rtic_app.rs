@@ -0,0 +1,189 @@
+//! RTIC Application Variant
+//!
+//! This module provides an alternative entry point built on RTIC 1.x, enabled via
+//! the `rtic` cargo feature. Instead of the hand-rolled `Mutex<RefCell<Option<_>>>`
+//! globals used by the bare-metal build in `main.rs`, the FSM+context, the USB
+//! device/serial objects, and the ADC/timer peripherals are declared as RTIC
+//! `#[shared]`/`#[local]` resources. RTIC generates the priority-based locking and
+//! NVIC setup for us, so resources are race-free by construction and there is no
+//! `Option` "dance" to publish state after `init`.
+//!
+//! Build with `--no-default-features --features rtic` (the `rtic` feature is
+//! mutually exclusive with the default polling build in `main.rs`).
+
+#![cfg(feature = "rtic")]
+
+#[rtic::app(device = rp235x_hal::pac, peripherals = true)]
+mod app {
+    use rp235x_hal as hal;
+    use hal::pac;
+    use usb_device::prelude::*;
+    use usbd_serial::SerialPort;
+
+    use crate::blinky_fsm::{BlinkyContext, BlinkyEvent, BlinkyFsm};
+
+    const XTAL_FREQ_HZ: u32 = 12_000_000u32;
+
+    type UsbBusType = hal::usb::UsbBus;
+
+    /// Concrete type of `pins.gpio26` as handed to `hal::adc::AdcPin::new`,
+    /// i.e. its reset-state type straight out of `hal::gpio::Pins::new`.
+    type AdcInputPin = hal::gpio::Pin<hal::gpio::bank0::Gpio26, hal::gpio::FunctionNull, hal::gpio::PullDown>;
+
+    /// Resources shared across more than one task; RTIC locks these for us.
+    #[shared]
+    struct Shared {
+        fsm: BlinkyFsm,
+        ctx: BlinkyContext,
+        usb_dev: UsbDevice<'static, UsbBusType>,
+        usb_serial: SerialPort<'static, UsbBusType>,
+    }
+
+    /// Resources owned by a single task; no locking required.
+    #[local]
+    struct Local {
+        adc: hal::Adc,
+        adc_pin: hal::adc::AdcPin<AdcInputPin>,
+        alarm: hal::timer::Alarm0,
+    }
+
+    #[init(local = [usb_bus: Option<usb_device::bus::UsbBusAllocator<UsbBusType>> = None])]
+    fn init(cx: init::Context) -> (Shared, Local) {
+        let mut pac = cx.device;
+        let mut watchdog = hal::Watchdog::new(pac.WATCHDOG);
+        let watchdog_caused_reset = watchdog.caused_reboot();
+
+        let clocks = hal::clocks::init_clocks_and_plls(
+            XTAL_FREQ_HZ,
+            pac.XOSC,
+            pac.CLOCKS,
+            pac.PLL_SYS,
+            pac.PLL_USB,
+            &mut pac.RESETS,
+            &mut watchdog,
+        )
+        .unwrap();
+
+        let mut timer = hal::Timer::new_timer0(pac.TIMER0, &mut pac.RESETS, &clocks);
+        let mut alarm = timer.alarm_0().unwrap();
+        let _ = alarm.schedule(fugit::ExtU32::millis(200));
+        alarm.enable_interrupt();
+
+        let sio = hal::Sio::new(pac.SIO);
+        let pins = hal::gpio::Pins::new(
+            pac.IO_BANK0,
+            pac.PADS_BANK0,
+            sio.gpio_bank0,
+            &mut pac.RESETS,
+        );
+        let led = pins.gpio15.into_push_pull_output();
+
+        let adc = hal::Adc::new(pac.ADC, &mut pac.RESETS);
+        let adc_pin = hal::adc::AdcPin::new(pins.gpio26).unwrap();
+
+        let usb_bus = cx
+            .local
+            .usb_bus
+            .insert(usb_device::bus::UsbBusAllocator::new(hal::usb::UsbBus::new(
+                pac.USB,
+                pac.USB_DPRAM,
+                clocks.usb_clock,
+                true,
+                &mut pac.RESETS,
+            )));
+
+        let mut usb_serial = SerialPort::new(usb_bus);
+        let usb_dev = UsbDeviceBuilder::new(usb_bus, UsbVidPid(0x16c0, 0x27dd))
+            .strings(&[StringDescriptors::default()
+                .manufacturer("Raspberry Pi")
+                .product("Pico 2 W ADC Demo (RTIC)")
+                .serial_number("ADC001")])
+            .unwrap()
+            .device_class(usbd_serial::USB_CLASS_CDC)
+            .build();
+
+        // Arm the watchdog last, after the bring-up above, for the same reason
+        // as the bare-metal build: see `hardware::init`.
+        watchdog.start(fugit::ExtU32::millis(500));
+
+        let mut ctx = BlinkyContext {
+            led,
+            wait_ticks: 0,
+            last_adc_value: 0,
+            high_threshold: crate::blinky_fsm::DEFAULT_HIGH_THRESHOLD,
+            wait_limit: crate::blinky_fsm::DEFAULT_WAIT_LIMIT,
+            dropped_events: 0,
+            watchdog,
+            fault_message: None,
+        };
+        let mut fsm = BlinkyFsm::LedOff;
+        fsm.init(&mut ctx);
+
+        // If the previous reset was the watchdog firing, start in `Fault`
+        // instead of resuming into `LedOff` as though nothing happened.
+        // `usb_serial` is still a plain local here (not yet moved into
+        // `Shared`), so this can write to it directly.
+        if watchdog_caused_reset {
+            fsm.dispatch(&mut ctx, &BlinkyEvent::WatchdogWarn);
+            if let Some(reason) = ctx.fault_message.take() {
+                let _ = usb_serial.write(reason.as_bytes());
+            }
+        }
+
+        (
+            Shared { fsm, ctx, usb_dev, usb_serial },
+            Local { adc, adc_pin, alarm },
+        )
+    }
+
+    /// Periodic tick: rearms the alarm, dispatches `TimerTick` into the FSM,
+    /// and pends `adc_fifo` to trigger the next conversion. This replaces the
+    /// raw `ADC::ptr().cs()` poke `trigger_adc()` uses in the bare-metal
+    /// single-shot build (see `blinky_fsm::trigger_adc`), which would otherwise race
+    /// `adc_fifo`'s own access to the same peripheral outside RTIC's lock.
+    #[task(binds = TIMER_IRQ_0, shared = [fsm, ctx, usb_serial], local = [alarm])]
+    fn tick(mut cx: tick::Context) {
+        cx.local.alarm.clear_interrupt();
+        let _ = cx.local.alarm.schedule(fugit::ExtU32::millis(200));
+
+        let fault_reason = (cx.shared.fsm, cx.shared.ctx).lock(|fsm, ctx| {
+            // Every tick reaching here means the scheduler is alive and
+            // this task is running, so it's the natural place to feed it.
+            ctx.watchdog.feed();
+            fsm.dispatch(ctx, &BlinkyEvent::TimerTick);
+            ctx.fault_message.take()
+        });
+        if let Some(reason) = fault_reason {
+            cx.shared.usb_serial.lock(|usb_serial| {
+                let _ = usb_serial.write(reason.as_bytes());
+            });
+        }
+
+        rtic::pend(pac::Interrupt::ADC_IRQ_FIFO);
+    }
+
+    /// Triggered by `tick`: reads one sample and dispatches `AdcResult`.
+    #[task(binds = ADC_IRQ_FIFO, shared = [fsm, ctx], local = [adc, adc_pin])]
+    fn adc_fifo(mut cx: adc_fifo::Context) {
+        let value: u16 = cx.local.adc.read(cx.local.adc_pin).unwrap_or(0);
+
+        (cx.shared.fsm, cx.shared.ctx).lock(|fsm, ctx| {
+            fsm.dispatch(ctx, &BlinkyEvent::AdcResult(value));
+        });
+    }
+
+    /// USB interrupt: polls the device and discards inbound bytes. Unlike
+    /// `usb_module::USBCTRL_IRQ` (which this build doesn't use — it drives
+    /// its own `usb_dev`/`usb_serial` resources above), this does not parse
+    /// the inbound line-oriented command console; the RTIC build has no
+    /// equivalent of the `tick`/`thresh`/`wait` USB commands yet.
+    #[task(binds = USBCTRL_IRQ, shared = [usb_dev, usb_serial])]
+    fn usbctrl(cx: usbctrl::Context) {
+        (cx.shared.usb_dev, cx.shared.usb_serial).lock(|usb_dev, usb_serial| {
+            if usb_dev.poll(&mut [usb_serial]) {
+                let mut buf = [0u8; 64];
+                let _ = usb_serial.read(&mut buf);
+            }
+        });
+    }
+}
@@ -4,9 +4,17 @@
 //! It manages the global static resources required for the USB stack,
 //! handles the initialization, and implements the `USBCTRL_IRQ` interrupt handler
 //! to ensure robust communication.
+//!
+//! Inbound bytes are also fed through a tiny line-oriented console protocol:
+//! the ISR accumulates them into `RX_LINE` until `\n`, parses the line into a
+//! [`UsbCommand`], and pushes it onto `COMMAND_QUEUE` for the main loop to
+//! apply via [`take_command`]. This turns the serial link into a two-way
+//! command/telemetry channel instead of a write-only log.
 
 use core::cell::RefCell;
 use critical_section::Mutex;
+use heapless::spsc::Queue;
+use heapless::String;
 use usb_device::prelude::*;
 use usbd_serial::SerialPort;
 
@@ -16,12 +24,32 @@ use hal::pac;
 // Select appropriate interrupt macro based on chip architecture
 use rp235x_hal::pac::interrupt;
 
+use crate::blinky_fsm::BlinkyEvent;
+
 type UsbBusType = hal::usb::UsbBus;
 
 // Global USB Objects (Mutex protected for ISR access)
 static USB_DEVICE: Mutex<RefCell<Option<UsbDevice<UsbBusType>>>> = Mutex::new(RefCell::new(None));
 static USB_SERIAL: Mutex<RefCell<Option<SerialPort<UsbBusType>>>> = Mutex::new(RefCell::new(None));
 
+/// Commands understood by the line-oriented USB console, one per line:
+/// - `tick` injects [`BlinkyEvent::TimerTick`]
+/// - `adc <n>` injects [`BlinkyEvent::AdcResult`] with value `n`
+/// - `thresh <n>` sets `BlinkyContext::high_threshold` to `n`
+/// - `wait <n>` sets `BlinkyContext::wait_limit` to `n`
+#[derive(Clone, Copy, Debug)]
+pub enum UsbCommand {
+    Inject(BlinkyEvent),
+    SetThreshold(u16),
+    SetWaitLimit(u32),
+}
+
+// Accumulates incoming bytes until a newline completes a command line.
+static RX_LINE: Mutex<RefCell<String<64>>> = Mutex::new(RefCell::new(String::new()));
+
+// Parsed commands awaiting pickup by the main loop via `take_command`.
+static COMMAND_QUEUE: Mutex<RefCell<Queue<UsbCommand, 8>>> = Mutex::new(RefCell::new(Queue::new()));
+
 /// Initialize USB Serial and enable the USB interrupt.
 ///
 /// This setup includes creating the static bus allocator using `unsafe` (safe pattern for no_std),
@@ -88,11 +116,44 @@ pub fn write(data: &[u8]) {
     });
 }
 
+/// Pop the next parsed console command, if any.
+///
+/// Called from the main loop (never from interrupt context) to drain commands
+/// queued by `USBCTRL_IRQ` and apply them to the FSM/context.
+pub fn take_command() -> Option<UsbCommand> {
+    critical_section::with(|cs| COMMAND_QUEUE.borrow_ref_mut(cs).dequeue())
+}
+
+/// Parse a single command line (already stripped of its trailing `\n`).
+///
+/// Returns `None` for blank lines or anything that doesn't match a known
+/// command/argument shape.
+fn parse_line(line: &str) -> Option<UsbCommand> {
+    let mut tokens = line.trim().split_whitespace();
+    match tokens.next()? {
+        "tick" => Some(UsbCommand::Inject(BlinkyEvent::TimerTick)),
+        "adc" => tokens
+            .next()?
+            .parse()
+            .ok()
+            .map(|value| UsbCommand::Inject(BlinkyEvent::AdcResult(value))),
+        "thresh" => tokens.next()?.parse().ok().map(UsbCommand::SetThreshold),
+        "wait" => tokens.next()?.parse().ok().map(UsbCommand::SetWaitLimit),
+        _ => None,
+    }
+}
+
 /// USB Interrupt Handler
 ///
 /// Handles all USB events (Enumeration, Data In/Out) automatically.
 /// By using an interrupt, we ensure the USB connection remains stable
-/// even if the main loop is busy.
+/// even if the main loop is busy. Inbound bytes are also assembled into
+/// command lines and parsed into `UsbCommand`s for `take_command`.
+///
+/// Only used by the bare-metal builds; the `rtic` and `embassy` variants
+/// drive their own USB device/serial objects instead (see `rtic_app.rs` and
+/// `embassy_app.rs`).
+#[cfg(not(any(feature = "rtic", feature = "embassy")))]
 #[allow(non_snake_case)]
 #[interrupt]
 fn USBCTRL_IRQ() {
@@ -102,9 +163,28 @@ fn USBCTRL_IRQ() {
 
         if let (Some(dev), Some(serial)) = (dev.as_mut(), serial.as_mut()) {
             if dev.poll(&mut [serial]) {
-                // Consume data to clear buffer (echo or logic could be added here)
                 let mut buf = [0u8; 64];
-                let _ = serial.read(&mut buf);
+                if let Ok(count) = serial.read(&mut buf) {
+                    let mut line = RX_LINE.borrow_ref_mut(cs);
+                    let mut queue = COMMAND_QUEUE.borrow_ref_mut(cs);
+
+                    for &byte in &buf[..count] {
+                        match byte {
+                            b'\n' => {
+                                if let Some(cmd) = parse_line(line.as_str()) {
+                                    let _ = queue.enqueue(cmd);
+                                }
+                                line.clear();
+                            }
+                            b'\r' => {} // ignore CR, lines are terminated by LF
+                            _ => {
+                                // Silently drop bytes past the line buffer's capacity
+                                // rather than losing the whole command.
+                                let _ = line.push(byte as char);
+                            }
+                        }
+                    }
+                }
             }
         }
     });